@@ -5,18 +5,115 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
 extern crate base64;
 extern crate byteorder;
+#[cfg(feature = "std")]
 extern crate chrono;
+extern crate getrandom;
+extern crate hmac;
 extern crate rand;
+extern crate sha2;
 #[macro_use]
 extern crate serde_derive;
 
-use byteorder::{LittleEndian, WriteBytesExt};
-use rand::{OsRng, RngCore};
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
 use std::fmt;
-use std::io::Cursor;
+#[cfg(feature = "std")]
 use std::ops::Deref;
+
+#[cfg(not(feature = "std"))]
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use core::ops::Deref;
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec, vec::Vec};
+
+use byteorder::{ByteOrder, LittleEndian};
+#[cfg(feature = "std")]
+use chrono::TimeZone;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Errors that can arise while generating or configuring a `TextNonce`.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The requested length was less than 16.
+    TooShort,
+    /// The requested length was not divisible by 4.
+    NotDivisibleBy4,
+    /// The supplied salt was empty.
+    EmptySalt,
+    /// The underlying randomness source failed.
+    GetRandom(getrandom::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::TooShort => write!(f, "length must be >= 16"),
+            Error::NotDivisibleBy4 => write!(f, "length must be divisible by 4"),
+            Error::EmptySalt => write!(f, "salt must not be empty"),
+            Error::GetRandom(ref e) => write!(f, "failed to obtain randomness: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl From<getrandom::Error> for Error {
+    fn from(e: getrandom::Error) -> Error {
+        Error::GetRandom(e)
+    }
+}
+
+/// A clock function returning `(seconds_since_epoch, subsec_nanos)`, used to source the
+/// time-prefix of a `TextNonce` in `no_std` environments that have no built-in clock.
+/// Under the default `std` feature, this is supplied automatically from
+/// `chrono::Utc::now()` and callers never need to provide one themselves.
+pub type ClockFn = fn() -> (i64, u32);
+
+#[cfg(feature = "std")]
+fn std_clock() -> (i64, u32) {
+    let now = chrono::Utc::now();
+    (now.timestamp(), now.timestamp_subsec_nanos())
+}
+
+/// Errors that can occur while parsing a `TextNonce` from its string form.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// The string was shorter than 16 characters, or its length was not
+    /// divisible by 4.
+    BadLength,
+    /// The string contained characters outside the chosen base64 alphabet.
+    BadAlphabet,
+    /// The decoded time prefix was not a valid point in time (e.g. the
+    /// nanoseconds component was out of range).
+    TimeOutOfRange,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::BadLength => write!(f, "length must be >= 16 and divisible by 4"),
+            ParseError::BadAlphabet => write!(f, "invalid base64 for the given configuration"),
+            ParseError::TimeOutOfRange => write!(f, "embedded time prefix is out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
 /// A nonce is a cryptographic concept of an arbitrary number that is never used more than once.
 ///
 /// `TextNonce` is a nonce because the first 16 characters represents the current time, which
@@ -33,6 +130,7 @@ pub struct TextNonce(pub String);
 impl TextNonce {
     /// Generate a new `TextNonce` with 16 characters of time and 16 characters of
     /// randomness
+    #[cfg(feature = "std")]
     pub fn new() -> TextNonce {
         TextNonce::sized(32).unwrap()
     }
@@ -40,62 +138,322 @@ impl TextNonce {
     /// Generate a new `TextNonce`. `length` must be at least 16, and divisible by 4.
     /// The first 16 characters come from the time component, and all characters
     /// after that will be random.
-    pub fn sized(length: usize) -> Result<TextNonce, String> {
+    #[cfg(feature = "std")]
+    pub fn sized(length: usize) -> Result<TextNonce, Error> {
         TextNonce::sized_configured(length, base64::STANDARD)
     }
 
     /// Generate a new `TextNonce` using the `URL_SAFE` variant of base64 (using '_' and '-')
     /// `length` must be at least 16, and divisible by 4.  The first 16 characters come
     /// from the time component, and all characters after that will be random.
-    pub fn sized_urlsafe(length: usize) -> Result<TextNonce, String> {
+    #[cfg(feature = "std")]
+    pub fn sized_urlsafe(length: usize) -> Result<TextNonce, Error> {
         TextNonce::sized_configured(length, base64::URL_SAFE)
     }
 
     /// Generate a new `TextNonce` specifying the Base64 configuration to use.
     /// `length` must be at least 16, and divisible by 4.  The first 16 characters come
     /// from the time component, and all characters after that will be random.
-    pub fn sized_configured(length: usize, config: base64::Config) -> Result<TextNonce, String> {
-        if length < 16 {
-            return Err("length must be >= 16".to_owned());
-        }
-        if length % 4 != 0 {
-            return Err("length must be divisible by 4".to_owned());
-        }
+    #[cfg(feature = "std")]
+    pub fn sized_configured(length: usize, config: base64::Config) -> Result<TextNonce, Error> {
+        TextNonce::sized_with_clock(length, std_clock, config)
+    }
 
-        let bytelength: usize = (length / 4) * 3;
+    /// Generate a new `TextNonce`, sourcing the time prefix from the supplied `clock`
+    /// instead of the system clock, and the random tail from `getrandom`. This is the
+    /// `no_std`-compatible core that [`TextNonce::sized_configured`] wraps under the
+    /// default `std` feature; `no_std` users (embedded, WASM with a registered
+    /// `getrandom` backend) call it directly with their own clock source.
+    ///
+    /// `length` must be at least 16, and divisible by 4.
+    pub fn sized_with_clock(
+        length: usize,
+        clock: ClockFn,
+        config: base64::Config,
+    ) -> Result<TextNonce, Error> {
+        let bytelength = validate_length(length)?;
 
-        let mut raw: Vec<u8> = Vec::with_capacity(bytelength);
-        unsafe {
-            raw.set_len(bytelength);
-        }
+        let mut raw: Vec<u8> = vec![0u8; bytelength];
+        write_time_prefix(&mut raw, clock);
+        getrandom::getrandom(&mut raw[12..bytelength])?;
 
-        // Get the first 12 bytes from the current time
-        {
-            let now = chrono::Utc::now();
-            let secs: i64 = now.timestamp();
-            let nsecs: u32 = now.timestamp_subsec_nanos();
+        Ok(TextNonce(base64::encode_config(&raw, config)))
+    }
 
-            let mut cursor = Cursor::new(&mut *raw);
-            cursor.write_u32::<LittleEndian>(nsecs).unwrap();
-            cursor.write_i64::<LittleEndian>(secs).unwrap();
+    /// Generate a new `TextNonce`, drawing the random portion from the supplied
+    /// `rng` instead of `getrandom`.  `length` must be at least 16, and divisible by 4.
+    /// The first 16 characters come from the time component, and all characters
+    /// after that will be random.
+    ///
+    /// This is useful for deterministic unit testing (pass a seeded RNG) or for
+    /// custom environments that want to supply their own CSPRNG.
+    #[cfg(feature = "std")]
+    pub fn sized_with_rng<R: RngCore>(
+        length: usize,
+        rng: &mut R,
+        config: base64::Config,
+    ) -> Result<TextNonce, Error> {
+        TextNonce::sized_with_rng_and_clock(length, rng, std_clock, config)
+    }
+
+    /// As [`TextNonce::sized_with_rng`], but sources the time prefix from the
+    /// supplied `clock` instead of the system clock, so `no_std` users can plug in
+    /// their own CSPRNG without needing `std`.
+    pub fn sized_with_rng_and_clock<R: RngCore>(
+        length: usize,
+        rng: &mut R,
+        clock: ClockFn,
+        config: base64::Config,
+    ) -> Result<TextNonce, Error> {
+        let bytelength = validate_length(length)?;
+
+        let mut raw: Vec<u8> = vec![0u8; bytelength];
+        write_time_prefix(&mut raw, clock);
+        rng.fill_bytes(&mut raw[12..bytelength]);
+
+        Ok(TextNonce(base64::encode_config(&raw, config)))
+    }
+
+    /// Generate a new `TextNonce` whose random tail is hashed together with a
+    /// caller-supplied `salt` via SHA-256 before base64 encoding, so the emitted
+    /// nonce stays diffused even if the randomness source is weak. `length` must be
+    /// at least 16, and divisible by 4; `salt` must not be empty.
+    #[cfg(feature = "std")]
+    pub fn salted(length: usize, salt: &[u8], config: base64::Config) -> Result<TextNonce, Error> {
+        TextNonce::salted_with_clock(length, salt, std_clock, config)
+    }
+
+    /// As [`TextNonce::salted`], but sources the time prefix from the supplied `clock`
+    /// instead of the system clock, for use in `no_std` environments.
+    pub fn salted_with_clock(
+        length: usize,
+        salt: &[u8],
+        clock: ClockFn,
+        config: base64::Config,
+    ) -> Result<TextNonce, Error> {
+        if salt.is_empty() {
+            return Err(Error::EmptySalt);
         }
 
-        // Get the last bytes from random data
-        match OsRng::new() {
-            Ok(mut g) => g.fill_bytes(&mut raw[12..bytelength]),
-            Err(_) => ::rand::thread_rng().fill_bytes(&mut raw[12..bytelength]),
-        };
+        let bytelength = validate_length(length)?;
+
+        let mut raw: Vec<u8> = vec![0u8; bytelength];
+        write_time_prefix(&mut raw, clock);
+
+        // Get the random tail as usual
+        let tail_len = bytelength - 12;
+        let mut tail = vec![0u8; tail_len];
+        getrandom::getrandom(&mut tail)?;
+
+        // Diffuse the random tail together with the salt through SHA-256
+        let diffused = salted_hash(&tail, salt, tail_len);
+        raw[12..bytelength].copy_from_slice(&diffused);
 
-        // base64 encode
         Ok(TextNonce(base64::encode_config(&raw, config)))
     }
 
+    /// Generate a `TextNonce` whose random tail is derived from `key` and `msg` via
+    /// the RFC 6979 HMAC construction instead of an RNG, so the same `(key, msg)`
+    /// always yields the same tail. The time prefix still varies; use
+    /// [`TextNonce::deterministic_timeless`] for a fully reproducible nonce.
+    /// `length` must be at least 16, and divisible by 4.
+    #[cfg(feature = "std")]
+    pub fn deterministic(key: &[u8], msg: &[u8], length: usize) -> Result<TextNonce, Error> {
+        TextNonce::deterministic_with_clock(key, msg, length, std_clock)
+    }
+
+    /// As [`TextNonce::deterministic`], but sources the time prefix from the supplied
+    /// `clock` instead of the system clock, for use in `no_std` environments.
+    pub fn deterministic_with_clock(
+        key: &[u8],
+        msg: &[u8],
+        length: usize,
+        clock: ClockFn,
+    ) -> Result<TextNonce, Error> {
+        let bytelength = validate_length(length)?;
+
+        let mut raw: Vec<u8> = vec![0u8; bytelength];
+        write_time_prefix(&mut raw, clock);
+
+        let tail = rfc6979_bytes(key, msg, bytelength - 12);
+        raw[12..bytelength].copy_from_slice(&tail);
+
+        Ok(TextNonce(base64::encode_config(&raw, base64::STANDARD)))
+    }
+
+    /// As [`TextNonce::deterministic`], but zeroes the 12-byte time prefix instead of
+    /// filling it with the current time, so that the entire nonce (not just the
+    /// random tail) is reproducible for a given `(key, msg)` pair.
+    pub fn deterministic_timeless(
+        key: &[u8],
+        msg: &[u8],
+        length: usize,
+    ) -> Result<TextNonce, Error> {
+        let bytelength = validate_length(length)?;
+
+        let mut raw: Vec<u8> = vec![0u8; bytelength];
+
+        let tail = rfc6979_bytes(key, msg, bytelength - 12);
+        raw[12..bytelength].copy_from_slice(&tail);
+
+        Ok(TextNonce(base64::encode_config(&raw, base64::STANDARD)))
+    }
+
+    /// Parse and validate a `TextNonce` received over the wire, checking that `s` has
+    /// a valid length (`>= 16` chars, divisible by 4), is valid base64 for the given
+    /// `config`, and embeds a well-formed time prefix.
+    #[cfg(feature = "std")]
+    pub fn parse(s: &str, config: base64::Config) -> Result<TextNonce, ParseError> {
+        let raw = decode_raw(s, config)?;
+        time_from_raw(&raw)?;
+        Ok(TextNonce(s.to_owned()))
+    }
+
+    /// Recover the time embedded in this nonce's 12-byte prefix, trying first the
+    /// standard then the URL-safe base64 alphabet, since `TextNonce` does not record
+    /// which one was used to encode it. Returns `None` if `self` isn't a well-formed
+    /// `TextNonce` (wrong length, bad alphabet, or an out-of-range time).
+    #[cfg(feature = "std")]
+    pub fn timestamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.timestamp_configured(base64::STANDARD)
+            .or_else(|| self.timestamp_configured(base64::URL_SAFE))
+    }
+
+    /// As [`TextNonce::timestamp`], but decodes using the given base64 `config`
+    /// rather than guessing between the standard and URL-safe alphabets.
+    #[cfg(feature = "std")]
+    pub fn timestamp_configured(&self, config: base64::Config) -> Option<chrono::DateTime<chrono::Utc>> {
+        let raw = decode_raw(&self.0, config).ok()?;
+        time_from_raw(&raw).ok()
+    }
+
+    /// How long ago this nonce's embedded timestamp was generated, relative to now.
+    /// Returns `None` if the timestamp can't be recovered (see
+    /// [`TextNonce::timestamp`]).
+    #[cfg(feature = "std")]
+    pub fn age(&self) -> Option<chrono::Duration> {
+        self.timestamp()
+            .map(|ts| chrono::Utc::now().signed_duration_since(ts))
+    }
+
     pub fn into_string(self) -> String {
         let TextNonce(s) = self;
         s
     }
 }
 
+/// Validate `length` and return the corresponding raw byte length, or an `Error` if
+/// the length is too short or not divisible by 4.
+fn validate_length(length: usize) -> Result<usize, Error> {
+    if length < 16 {
+        return Err(Error::TooShort);
+    }
+    if !length.is_multiple_of(4) {
+        return Err(Error::NotDivisibleBy4);
+    }
+    Ok((length / 4) * 3)
+}
+
+/// Write the 12-byte time prefix (little-endian nanoseconds, then little-endian
+/// seconds) from `clock` into the start of `raw`.
+fn write_time_prefix(raw: &mut [u8], clock: ClockFn) {
+    let (secs, nsecs) = clock();
+    LittleEndian::write_u32(&mut raw[0..4], nsecs);
+    LittleEndian::write_i64(&mut raw[4..12], secs);
+}
+
+/// Validate `s`'s length and base64 alphabet against `config`, returning the decoded
+/// raw bytes. Used by both [`TextNonce::parse`] and [`TextNonce::timestamp_configured`].
+#[cfg(feature = "std")]
+fn decode_raw(s: &str, config: base64::Config) -> Result<Vec<u8>, ParseError> {
+    if s.len() < 16 || !s.len().is_multiple_of(4) {
+        return Err(ParseError::BadLength);
+    }
+
+    let raw = base64::decode_config(s, config).map_err(|_| ParseError::BadAlphabet)?;
+    if raw.len() < 12 {
+        return Err(ParseError::BadLength);
+    }
+
+    Ok(raw)
+}
+
+/// Reconstruct the time embedded in a decoded `TextNonce`'s 12-byte prefix.
+#[cfg(feature = "std")]
+fn time_from_raw(raw: &[u8]) -> Result<chrono::DateTime<chrono::Utc>, ParseError> {
+    let nsecs = LittleEndian::read_u32(&raw[0..4]);
+    let secs = LittleEndian::read_i64(&raw[4..12]);
+    chrono::Utc
+        .timestamp_opt(secs, nsecs)
+        .single()
+        .ok_or(ParseError::TimeOutOfRange)
+}
+
+/// Derive `needed` bytes deterministically from `key` and `msg` using the RFC 6979
+/// HMAC-DRBG construction (HMAC-SHA256), looping the `V = HMAC_K(V)` step and
+/// concatenating output blocks until enough bytes are produced.
+fn rfc6979_bytes(key: &[u8], msg: &[u8], needed: usize) -> Vec<u8> {
+    let msg_hash = {
+        use sha2::Digest;
+        Sha256::digest(msg)
+    };
+
+    let hlen = 32;
+    let mut v = vec![0x01u8; hlen];
+    let mut k = vec![0x00u8; hlen];
+
+    let mut mac = HmacSha256::new_varkey(&k).unwrap();
+    mac.input(&v);
+    mac.input(&[0x00]);
+    mac.input(key);
+    mac.input(&msg_hash);
+    k = mac.result().code().to_vec();
+
+    let mut mac = HmacSha256::new_varkey(&k).unwrap();
+    mac.input(&v);
+    v = mac.result().code().to_vec();
+
+    let mut mac = HmacSha256::new_varkey(&k).unwrap();
+    mac.input(&v);
+    mac.input(&[0x01]);
+    mac.input(key);
+    mac.input(&msg_hash);
+    k = mac.result().code().to_vec();
+
+    let mut mac = HmacSha256::new_varkey(&k).unwrap();
+    mac.input(&v);
+    v = mac.result().code().to_vec();
+
+    let mut out = Vec::with_capacity(needed);
+    while out.len() < needed {
+        let mut mac = HmacSha256::new_varkey(&k).unwrap();
+        mac.input(&v);
+        v = mac.result().code().to_vec();
+        out.extend_from_slice(&v);
+    }
+    out.truncate(needed);
+    out
+}
+
+/// Diffuse `random` together with `salt` through SHA-256, expanding (by hashing a
+/// counter alongside them) or truncating the digest to exactly `needed` bytes.
+fn salted_hash(random: &[u8], salt: &[u8], needed: usize) -> Vec<u8> {
+    use sha2::Digest;
+
+    let mut out = Vec::with_capacity(needed);
+    let mut counter: u32 = 0;
+    while out.len() < needed {
+        let mut hasher = Sha256::new();
+        hasher.input(random);
+        hasher.input(salt);
+        hasher.input(counter.to_le_bytes());
+        out.extend_from_slice(hasher.result().as_slice());
+        counter += 1;
+    }
+    out.truncate(needed);
+    out
+}
+
 impl fmt::Display for TextNonce {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Display::fmt(&self.0, f)
@@ -105,7 +463,7 @@ impl fmt::Display for TextNonce {
 impl Deref for TextNonce {
     type Target = str;
     fn deref(&self) -> &str {
-        &*self.0
+        &self.0
     }
 }
 
@@ -130,7 +488,7 @@ mod tests {
             // Verify their character content
             assert_eq!(
                 s.chars()
-                    .filter(|x| x.is_digit(10) || x.is_alphabetic() || *x == '+' || *x == '/')
+                    .filter(|x| x.is_ascii_digit() || x.is_alphabetic() || *x == '+' || *x == '/')
                     .count(),
                 32
             );
@@ -154,9 +512,149 @@ mod tests {
         assert!(n.is_err());
     }
 
+    // A trivial deterministic `RngCore` that always fills with the same byte,
+    // used to prove `sized_with_rng` drives its randomness from the supplied
+    // generator rather than the OS RNG.
+    struct FixedRng(u8);
+
+    impl ::rand::RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            u32::from(self.0)
+        }
+        fn next_u64(&mut self) -> u64 {
+            u64::from(self.0)
+        }
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest.iter_mut() {
+                *byte = self.0;
+            }
+        }
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ::rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sized_with_rng() {
+        let mut rng = FixedRng(0x42);
+        let n = TextNonce::sized_with_rng(32, &mut rng, base64::STANDARD);
+        assert!(n.is_ok());
+        let TextNonce(s) = n.unwrap();
+        assert_eq!(s.len(), 32);
+
+        // Two nonces generated back to back with the same fixed rng should
+        // only differ in their time-derived prefix, never in the random tail.
+        let mut rng2 = FixedRng(0x42);
+        let TextNonce(s2) = TextNonce::sized_with_rng(32, &mut rng2, base64::STANDARD).unwrap();
+        assert_eq!(s[16..], s2[16..]);
+    }
+
+    #[test]
+    fn sized_with_rng_and_clock() {
+        fn fixed_clock() -> (i64, u32) {
+            (1_600_000_000, 0)
+        }
+
+        let mut rng = FixedRng(0x42);
+        let n =
+            TextNonce::sized_with_rng_and_clock(32, &mut rng, fixed_clock, base64::STANDARD);
+        assert!(n.is_ok());
+        let TextNonce(s) = n.unwrap();
+        assert_eq!(s.len(), 32);
+    }
+
+    #[test]
+    fn salted() {
+        let n = TextNonce::salted(32, b"device-id-1234", base64::STANDARD);
+        assert!(n.is_ok());
+        let TextNonce(s) = n.unwrap();
+        assert_eq!(s.len(), 32);
+
+        let n = TextNonce::salted(32, b"", base64::STANDARD);
+        assert!(n.is_err());
+    }
+
+    #[test]
+    fn deterministic() {
+        let key = b"secret-key";
+        let msg = b"message to sign";
+
+        let n1 = TextNonce::deterministic(key, msg, 32).unwrap();
+        let n2 = TextNonce::deterministic(key, msg, 32).unwrap();
+        // The time prefix differs, but the random tail must match.
+        assert_eq!(n1[16..], n2[16..]);
+
+        let n3 = TextNonce::deterministic(key, b"a different message", 32).unwrap();
+        assert_ne!(n1[16..], n3[16..]);
+    }
+
+    #[test]
+    fn deterministic_timeless() {
+        let key = b"secret-key";
+        let msg = b"message to sign";
+
+        let n1 = TextNonce::deterministic_timeless(key, msg, 32).unwrap();
+        let n2 = TextNonce::deterministic_timeless(key, msg, 32).unwrap();
+        // With the time prefix zeroed, the whole nonce is reproducible.
+        assert_eq!(n1, n2);
+    }
+
+    #[test]
+    fn sized_with_clock() {
+        fn fixed_clock() -> (i64, u32) {
+            (1_600_000_000, 0)
+        }
+
+        let n1 = TextNonce::sized_with_clock(32, fixed_clock, base64::STANDARD).unwrap();
+        let n2 = TextNonce::sized_with_clock(32, fixed_clock, base64::STANDARD).unwrap();
+        // Same clock, so the time prefix matches; the random tail still varies.
+        assert_eq!(n1[..16], n2[..16]);
+    }
+
+    #[test]
+    fn parse_roundtrip() {
+        let n = TextNonce::sized(32).unwrap();
+        let parsed = TextNonce::parse(&n, base64::STANDARD).unwrap();
+        assert_eq!(n, parsed);
+        assert!(parsed.timestamp().is_some());
+        assert!(parsed.age().unwrap().num_seconds() < 5);
+    }
+
+    #[test]
+    fn parse_urlsafe_roundtrip() {
+        let n = TextNonce::sized_urlsafe(32).unwrap();
+        let parsed = TextNonce::parse(&n, base64::URL_SAFE).unwrap();
+        assert_eq!(n, parsed);
+        // `timestamp()` must find the embedded time even though it was
+        // encoded with the URL-safe alphabet, not the standard one.
+        assert!(parsed.timestamp().is_some());
+    }
+
+    #[test]
+    fn parse_bad_length() {
+        assert_eq!(
+            TextNonce::parse("short", base64::STANDARD).unwrap_err(),
+            super::ParseError::BadLength
+        );
+        assert_eq!(
+            TextNonce::parse("not-divisible-by-4!", base64::STANDARD).unwrap_err(),
+            super::ParseError::BadLength
+        );
+    }
+
+    #[test]
+    fn parse_bad_alphabet() {
+        // `!` isn't in the base64 alphabet at all.
+        assert_eq!(
+            TextNonce::parse("!!!!!!!!!!!!!!!!", base64::STANDARD).unwrap_err(),
+            super::ParseError::BadAlphabet
+        );
+    }
+
     #[test]
     fn serde() {
-        let n = TextNonce::sized(48);
+        let n = TextNonce::sized(48).unwrap();
         let serialized = bincode::serialize(&n, bincode::Infinite).unwrap();
         let deserialized = bincode::deserialize(&serialized).unwrap();
         assert_eq!(n, deserialized);